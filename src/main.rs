@@ -1,15 +1,102 @@
 use arboard::{Clipboard, ImageData, SetExtLinux};
-use screenshots::{image, Screen};
+use base64::Engine;
+use screenshots::{image, image::ImageEncoder, Screen};
 use std::{
     env,
+    io::Write,
+    path::{Path, PathBuf},
     process::{Command, ExitCode, Stdio},
-    time::SystemTime,
+    time::Duration,
 };
 
+/// OSC 52 payloads above this size are dropped by many terminal emulators.
+const OSC52_SAFE_PAYLOAD_BYTES: usize = 74 * 1024;
+
 type AnyError = Box<dyn std::error::Error>;
 
 const SELF_IS_DAEMONIZED: &str = "__self_is_daemonized";
 
+/// Which part of the desktop a capture should cover.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ScreenshotKind {
+    Full,
+    Area,
+    Window,
+}
+
+/// Which screen(s) a `ScreenshotKind::Full` capture should cover.
+#[derive(Clone, Copy)]
+enum ScreenTarget {
+    Index(usize),
+    All,
+}
+
+/// Everything that shapes a single capture, bundled so it can be threaded through
+/// `run()`, the daemonize re-exec, and the capture/clipboard functions as one unit.
+#[derive(Clone, Copy)]
+struct CaptureOptions {
+    kind: ScreenshotKind,
+    target: ScreenTarget,
+    osc52: bool,
+    delay_secs: u64,
+    cursor: bool,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        CaptureOptions {
+            kind: ScreenshotKind::Full,
+            target: ScreenTarget::Index(0),
+            osc52: false,
+            delay_secs: 0,
+            cursor: false,
+        }
+    }
+}
+
+impl CaptureOptions {
+    /// Packs the options into a single `:`-separated argument for the daemonize re-exec.
+    fn encode(&self) -> String {
+        let kind = match self.kind {
+            ScreenshotKind::Full => "full",
+            ScreenshotKind::Area => "area",
+            ScreenshotKind::Window => "window",
+        };
+        let target = match self.target {
+            ScreenTarget::All => "all".to_string(),
+            ScreenTarget::Index(i) => i.to_string(),
+        };
+
+        format!("{kind}:{target}:{}:{}:{}", self.osc52, self.delay_secs, self.cursor)
+    }
+
+    fn decode(encoded: &str) -> Self {
+        let mut parts = encoded.split(':');
+
+        let kind = match parts.next() {
+            Some("area") => ScreenshotKind::Area,
+            Some("window") => ScreenshotKind::Window,
+            _ => ScreenshotKind::Full,
+        };
+        let target = match parts.next() {
+            Some("all") => ScreenTarget::All,
+            Some(n) => ScreenTarget::Index(n.parse().unwrap_or(0)),
+            None => ScreenTarget::Index(0),
+        };
+        let osc52 = parts.next() == Some("true");
+        let delay_secs = parts.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+        let cursor = parts.next() == Some("true");
+
+        CaptureOptions {
+            kind,
+            target,
+            osc52,
+            delay_secs,
+            cursor,
+        }
+    }
+}
+
 fn usage() -> String {
     format!(
         r#"If no subcommand is provided, it will take a screenshot of the current screen and save it to the system clipboard.
@@ -17,23 +104,349 @@ Usage:
     {it} [SUBCOMMAND]
 
 Subcommands:
-    help - Show this message.
-    save - Try to save a screenshot from the clipboard as a PNG file."#,
+    help       - Show this message.
+    save       - Try to save a screenshot from the clipboard as an image file.
+        --format png|jpeg|webp  - Output format (default: png).
+        --output <path-or-dir>  - Where to save the file (default: current directory).
+        --template <pattern>    - Filename pattern expanded with the current local
+                                   time, e.g. "spit-%Y%m%d-%H%M%S" (strftime syntax).
+        --quality <0-100>       - JPEG quality (default: 85).
+    area       - Select a region of the screen to capture.
+    window     - Select a window to capture.
+    all        - Capture every screen, stitched into one image.
+    screens    - List available screens and their geometry.
+    --screen N - Capture only screen N (see `screens` for indices).
+    --osc52    - Copy via an OSC 52 escape sequence instead of the system clipboard
+                 (auto-enabled over SSH).
+    --delay N  - Wait N seconds before capturing.
+    --cursor   - Include the mouse cursor in the capture. Honoured by grim and maim
+                 selections and, on Wayland, by a GNOME Shell capture of the whole
+                 desktop (which ignores --screen); other captures leave it out.
+
+Defaults for the `save` flags can be set in a config file under the platform config
+dir (e.g. ~/.config/spit/config on Linux) as `key = value` lines: format, output,
+template, quality."#,
         it = env::current_exe()
             .expect("name of the current program")
             .display()
     )
 }
 
-fn capture_screenshot() -> Result<image::RgbaImage, AnyError> {
-    let screen = Screen::all()?[0];
-    let image = screen.capture()?;
+/// Prints the index and geometry of every detected screen.
+fn list_screens() -> Result<(), AnyError> {
+    for (i, screen) in Screen::all()?.iter().enumerate() {
+        let info = screen.display_info;
+        println!(
+            "{i}: {}x{} at ({}, {})",
+            info.width, info.height, info.x, info.y
+        );
+    }
+
+    Ok(())
+}
+
+/// Captures every screen and blits them into one composite image sized to their
+/// combined bounding box, positioned at each screen's relative offset.
+fn capture_all_screens() -> Result<image::RgbaImage, AnyError> {
+    let screens = Screen::all()?;
+
+    let min_x = screens.iter().map(|s| s.display_info.x).min().unwrap_or(0);
+    let min_y = screens.iter().map(|s| s.display_info.y).min().unwrap_or(0);
+    let max_x = screens
+        .iter()
+        .map(|s| s.display_info.x + s.display_info.width as i32)
+        .max()
+        .unwrap_or(0);
+    let max_y = screens
+        .iter()
+        .map(|s| s.display_info.y + s.display_info.height as i32)
+        .max()
+        .unwrap_or(0);
+
+    let mut canvas = image::RgbaImage::new((max_x - min_x) as u32, (max_y - min_y) as u32);
+
+    for screen in screens {
+        let capture = screen.capture()?;
+        let offset_x = (screen.display_info.x - min_x) as u32;
+        let offset_y = (screen.display_info.y - min_y) as u32;
+        image::imageops::overlay(&mut canvas, &capture, offset_x as i64, offset_y as i64);
+    }
+
+    Ok(canvas)
+}
+
+/// Whether we're running under Wayland or X11, as far as `XDG_SESSION_TYPE` knows.
+fn is_wayland_session() -> bool {
+    env::var("XDG_SESSION_TYPE").is_ok_and(|v| v.eq_ignore_ascii_case("wayland"))
+}
+
+/// Finds the first of `candidates` that exists on `$PATH`.
+fn find_tool<'a>(candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .find(|name| {
+            Command::new("which")
+                .arg(name)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|s| s.success())
+        })
+        .copied()
+}
+
+/// Runs an interactive selection tool and loads the resulting PNG from `path`.
+fn capture_with_tool(
+    kind: ScreenshotKind,
+    cursor: bool,
+    path: &Path,
+) -> Result<image::RgbaImage, AnyError> {
+    let path_str = path.to_str().ok_or("temp screenshot path is not valid UTF-8")?;
+
+    if is_wayland_session() {
+        if find_tool(&["grim"]).is_some() && find_tool(&["slurp"]).is_some() {
+            let geometry = Command::new("slurp").output()?;
+            if !geometry.status.success() {
+                return Err("slurp selection was cancelled".into());
+            }
+            let geometry = String::from_utf8(geometry.stdout)?;
+            let geometry = geometry.trim();
+
+            let mut cmd = Command::new("grim");
+            if cursor {
+                cmd.arg("-c");
+            }
+            if kind == ScreenshotKind::Area || kind == ScreenshotKind::Window {
+                cmd.arg("-g").arg(geometry);
+            }
+            cmd.arg(path_str);
+            if !cmd.status()?.success() {
+                return Err("grim failed to capture the selection".into());
+            }
+        } else if find_tool(&["grimshot"]).is_some() {
+            let subcmd = match kind {
+                ScreenshotKind::Area => "area",
+                ScreenshotKind::Window => "active",
+                ScreenshotKind::Full => "screen",
+            };
+            if !Command::new("grimshot")
+                .args(["save", subcmd, path_str])
+                .status()?
+                .success()
+            {
+                return Err("grimshot failed to capture the selection".into());
+            }
+        } else {
+            return Err("no Wayland screenshot tool (grim/slurp or grimshot) found".into());
+        }
+    } else if let Some(tool) = find_tool(&["maim", "import"]) {
+        match tool {
+            "maim" => {
+                let mut cmd = Command::new("maim");
+                if !cursor {
+                    cmd.arg("-u");
+                }
+                if kind == ScreenshotKind::Area {
+                    cmd.arg("-s");
+                } else if kind == ScreenshotKind::Window {
+                    let window_id = Command::new("xdotool").arg("getactivewindow").output()?;
+                    if !window_id.status.success() {
+                        return Err("xdotool getactivewindow failed".into());
+                    }
+                    let window_id = String::from_utf8(window_id.stdout)?;
+                    cmd.args(["-i", window_id.trim()]);
+                }
+                cmd.arg(path_str);
+                if !cmd.status()?.success() {
+                    return Err("maim failed to capture the selection".into());
+                }
+            }
+            "import" => {
+                if !Command::new("import")
+                    .arg(path_str)
+                    .status()?
+                    .success()
+                {
+                    return Err("import failed to capture the selection".into());
+                }
+            }
+            _ => unreachable!(),
+        }
+    } else {
+        return Err("no X11 screenshot tool (maim or import) found".into());
+    }
+
+    let image = image::open(path)?.into_rgba8();
+    let _ = std::fs::remove_file(path);
+
+    Ok(image)
+}
+
+/// Captures the full desktop with the cursor composited in via the GNOME Shell
+/// D-Bus screenshot API, which (unlike direct framebuffer capture) can draw the
+/// pointer into the image on Wayland.
+fn capture_via_gnome_dbus(include_cursor: bool) -> Result<image::RgbaImage, AnyError> {
+    let tmp = env::temp_dir().join(format!("spit-gnome-{}.png", std::process::id()));
+    let tmp_str = tmp.to_str().ok_or("temp screenshot path is not valid UTF-8")?;
+
+    let status = Command::new("gdbus")
+        .args([
+            "call",
+            "--session",
+            "--dest",
+            "org.gnome.Shell",
+            "--object-path",
+            "/org/gnome/Shell/Screenshot",
+            "--method",
+            "org.gnome.Shell.Screenshot.Screenshot",
+            &include_cursor.to_string(),
+            "false",
+            tmp_str,
+        ])
+        .status()?;
+
+    if !status.success() {
+        return Err("org.gnome.Shell.Screenshot.Screenshot call failed".into());
+    }
+
+    let image = image::open(&tmp)?.into_rgba8();
+    let _ = std::fs::remove_file(&tmp);
+
+    Ok(image)
+}
+
+/// Plays the freedesktop screenshot sound, matching native screenshot UX. Best-effort:
+/// a missing `pw-play` or sound theme should never fail the capture.
+fn play_shutter_sound() {
+    let _ = Command::new("pw-play")
+        .arg("/usr/share/sounds/freedesktop/stereo/screen-capture.oga")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+fn capture_screenshot(opts: CaptureOptions) -> Result<image::RgbaImage, AnyError> {
+    if opts.delay_secs > 0 {
+        std::thread::sleep(Duration::from_secs(opts.delay_secs));
+    }
+
+    if cfg!(target_os = "linux") && opts.kind == ScreenshotKind::Full && opts.cursor && is_wayland_session()
+    {
+        if let Ok(image) = capture_via_gnome_dbus(true) {
+            play_shutter_sound();
+            return Ok(image);
+        }
+        eprintln!("WARNING: GNOME Shell screenshot D-Bus call failed, falling back");
+    }
+
+    if cfg!(target_os = "linux") && opts.kind != ScreenshotKind::Full {
+        let tmp = env::temp_dir().join(format!("spit-select-{}.png", std::process::id()));
+        let image = match capture_with_tool(opts.kind, opts.cursor, &tmp) {
+            Ok(image) => image,
+            Err(_) => {
+                eprintln!("WARNING: falling back to full-screen capture");
+                return capture_screenshot(CaptureOptions {
+                    kind: ScreenshotKind::Full,
+                    delay_secs: 0,
+                    ..opts
+                });
+            }
+        };
+        play_shutter_sound();
+        return Ok(image);
+    }
+
+    // Direct framebuffer capture (X11, or Wayland without GNOME's Shell D-Bus method)
+    // has no way to draw the pointer into the image, so `--cursor` has no effect here.
+    let image = match opts.target {
+        ScreenTarget::All => capture_all_screens()?,
+        ScreenTarget::Index(i) => {
+            let screen = *Screen::all()?.get(i).ok_or(format!("no screen at index {i}"))?;
+            screen.capture()?
+        }
+    };
+    play_shutter_sound();
 
     Ok(image)
 }
 
-fn screenshot_into_clipboard(clipboard: &mut Clipboard) -> Result<(), AnyError> {
-    let image = capture_screenshot()?;
+/// Whether we're attached to a remote shell, as far as SSH's own env vars know.
+fn is_ssh_session() -> bool {
+    env::var("SSH_CONNECTION").is_ok() || env::var("SSH_TTY").is_ok()
+}
+
+/// Emits `image` as an OSC 52 escape sequence so the terminal forwards it to the
+/// user's local clipboard. Falls back to saving a PNG when the encoded payload is
+/// too large for terminals to accept.
+fn copy_image_via_osc52(image: &image::RgbaImage) -> Result<(), AnyError> {
+    let mut png_bytes = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut png_bytes), image::ImageFormat::Png)?;
+
+    let encoded = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
+    if encoded.len() > OSC52_SAFE_PAYLOAD_BYTES {
+        eprintln!("WARNING: screenshot is too large for OSC 52, saving a PNG instead");
+        return save_image(image, &SaveOptions::default());
+    }
+
+    print!("\x1b]52;c;{encoded}\x07");
+    std::io::stdout().flush()?;
+
+    Ok(())
+}
+
+/// Whether we're running inside WSL, where `arboard` cannot reach the Windows clipboard.
+fn is_wsl() -> bool {
+    ["/proc/sys/kernel/osrelease", "/proc/version"].iter().any(|path| {
+        std::fs::read_to_string(path)
+            .map(|contents| {
+                let contents = contents.to_lowercase();
+                contents.contains("microsoft") || contents.contains("wsl")
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// Pushes `image` onto the Windows clipboard via `powershell.exe`, for use under WSL
+/// where `arboard` has no local clipboard to write to. Goes through
+/// `System.Windows.Forms.Clipboard::SetImage` rather than `Set-Clipboard -Path`, since
+/// the latter puts a file reference on the clipboard instead of an image bitmap.
+fn copy_image_via_wsl_clipboard(image: &image::RgbaImage) -> Result<(), AnyError> {
+    let tmp = env::temp_dir().join(format!("spit-wsl-{}.png", std::process::id()));
+    image.save_with_format(&tmp, image::ImageFormat::Png)?;
+
+    let win_path = Command::new("wslpath").args(["-w", &tmp.to_string_lossy()]).output()?;
+    let win_path = String::from_utf8(win_path.stdout)?;
+    let win_path = win_path.trim();
+
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms; \
+         Add-Type -AssemblyName System.Drawing; \
+         [System.Windows.Forms.Clipboard]::SetImage([System.Drawing.Image]::FromFile('{win_path}'))"
+    );
+    let status = Command::new("powershell.exe")
+        .args(["-NoProfile", "-STA", "-Command"])
+        .arg(script)
+        .status();
+
+    let _ = std::fs::remove_file(&tmp);
+
+    if !status?.success() {
+        return Err("powershell.exe Clipboard::SetImage failed".into());
+    }
+
+    Ok(())
+}
+
+fn screenshot_into_clipboard(clipboard: &mut Clipboard, opts: CaptureOptions) -> Result<(), AnyError> {
+    let image = capture_screenshot(opts)?;
+
+    if cfg!(target_os = "linux") && (opts.osc52 || is_ssh_session()) {
+        return copy_image_via_osc52(&image);
+    }
+
+    if cfg!(target_os = "linux") && is_wsl() {
+        return copy_image_via_wsl_clipboard(&image);
+    }
 
     let image_data = ImageData {
         width: image.width() as usize,
@@ -63,53 +476,328 @@ fn get_image_from_clipboard(clipboard: &mut Clipboard) -> Result<image::RgbaImag
     Ok(image)
 }
 
-fn save_image_cwd_as_png(image: &image::RgbaImage) -> Result<(), AnyError> {
-    let now = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)?
-        .as_secs();
-    let file_path = format!("{now}.png");
-    image.save_with_format(&file_path, image::ImageFormat::Png)?;
-    println!("Image from clipboard is saved as \"{file_path}\"");
+/// Where a saved screenshot should go and how it should be encoded.
+#[derive(Clone)]
+struct SaveOptions {
+    format: image::ImageFormat,
+    quality: u8,
+    output: Option<PathBuf>,
+    template: String,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        SaveOptions {
+            format: image::ImageFormat::Png,
+            quality: 85,
+            output: None,
+            template: "spit-%Y%m%d-%H%M%S".to_string(),
+        }
+    }
+}
+
+fn parse_image_format(name: &str) -> Option<image::ImageFormat> {
+    match name.to_lowercase().as_str() {
+        "png" => Some(image::ImageFormat::Png),
+        "jpeg" | "jpg" => Some(image::ImageFormat::Jpeg),
+        "webp" => Some(image::ImageFormat::WebP),
+        _ => None,
+    }
+}
+
+fn extension_for_format(format: image::ImageFormat) -> &'static str {
+    match format {
+        image::ImageFormat::Jpeg => "jpg",
+        image::ImageFormat::WebP => "webp",
+        _ => "png",
+    }
+}
+
+/// Path to spit's config file under the platform's config dir (e.g.
+/// `~/.config/spit/config` on Linux), if one can be determined for this platform.
+fn config_file_path() -> Option<PathBuf> {
+    let config_dir = if cfg!(target_os = "linux") {
+        env::var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|_| env::var("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .ok()
+    } else if cfg!(target_os = "macos") {
+        env::var("HOME")
+            .map(|home| PathBuf::from(home).join("Library/Application Support"))
+            .ok()
+    } else {
+        env::var("APPDATA").map(PathBuf::from).ok()
+    };
+
+    config_dir.map(|dir| dir.join("spit").join("config"))
+}
+
+/// Reads `key = value` defaults from the config file, falling back to
+/// `SaveOptions::default()` for anything missing or unparsable.
+fn load_save_config() -> SaveOptions {
+    let mut opts = SaveOptions::default();
+
+    let Some(path) = config_file_path() else {
+        return opts;
+    };
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return opts;
+    };
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        match key.trim() {
+            "format" => {
+                if let Some(format) = parse_image_format(value.trim()) {
+                    opts.format = format;
+                }
+            }
+            "output" => opts.output = Some(PathBuf::from(value.trim())),
+            "template" => opts.template = value.trim().to_string(),
+            "quality" => {
+                if let Ok(quality) = value.trim().parse() {
+                    opts.quality = quality;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    opts
+}
+
+/// Expands strftime-style specifiers in `template` against the current local time.
+fn expand_filename_template(template: &str) -> String {
+    chrono::Local::now().format(template).to_string()
+}
+
+/// Resolves the final save path: `--output` pointing at a file is used as-is,
+/// `--output` pointing at a directory gets the templated filename appended, and no
+/// `--output` falls back to the current directory.
+fn resolve_save_path(opts: &SaveOptions) -> PathBuf {
+    let filename = format!(
+        "{}.{}",
+        expand_filename_template(&opts.template),
+        extension_for_format(opts.format)
+    );
+
+    match &opts.output {
+        Some(dir) if dir.is_dir() => dir.join(filename),
+        Some(path) => path.clone(),
+        None => PathBuf::from(filename),
+    }
+}
+
+fn save_image(image: &image::RgbaImage, opts: &SaveOptions) -> Result<(), AnyError> {
+    let file_path = resolve_save_path(opts);
+
+    if opts.format == image::ImageFormat::Jpeg {
+        // JPEG has no alpha channel, so drop it before handing the image to the encoder.
+        let rgb = image::DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+        let mut file = std::fs::File::create(&file_path)?;
+        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut file, opts.quality);
+        encoder.write_image(
+            &rgb,
+            rgb.width(),
+            rgb.height(),
+            image::ColorType::Rgb8,
+        )?;
+    } else {
+        image.save_with_format(&file_path, opts.format)?;
+    }
+
+    println!("Image is saved as \"{}\"", file_path.display());
 
     Ok(())
 }
 
-fn save_image_from_clipboard(clipboard: &mut Clipboard) -> Result<(), AnyError> {
+fn save_image_from_clipboard(clipboard: &mut Clipboard, opts: &SaveOptions) -> Result<(), AnyError> {
     let image = get_image_from_clipboard(clipboard)?;
-    save_image_cwd_as_png(&image)?;
+    save_image(&image, opts)?;
 
     Ok(())
 }
 
-fn run() -> Result<(), AnyError> {
-    let mut clipboard = Clipboard::new()?;
+/// Re-spawns the current executable in the background, detached from the terminal,
+/// so that interactive selection and clipboard ownership survive the parent exiting.
+fn daemonize(opts: CaptureOptions) -> Result<(), AnyError> {
+    Command::new(env::current_exe()?)
+        .arg(SELF_IS_DAEMONIZED)
+        .arg(opts.encode())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .current_dir("/")
+        .spawn()?;
 
-    if let Some(subcmd) = env::args().nth(1) {
-        match subcmd.as_str() {
-            "help" => return Ok(println!("{}", usage())),
-            "save" => save_image_from_clipboard(&mut clipboard),
-            SELF_IS_DAEMONIZED if cfg!(target_os = "linux") => {
-                screenshot_into_clipboard(&mut clipboard)
-            }
-            _ => Err(format!(r#"Unknown subcommand "{subcmd}"{usage}"#, usage = usage()).into()),
-        }?;
+    Ok(())
+}
 
-        return Ok(());
+/// Parses `save`'s `--format`, `--output`, `--template` and `--quality` flags, layered
+/// on top of whatever the config file provides.
+fn parse_save_opts() -> Result<SaveOptions, AnyError> {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = load_save_config();
+
+    if let Some(i) = args.iter().position(|a| a == "--format") {
+        let name = args.get(i + 1).ok_or("--format requires png, jpeg, or webp")?;
+        opts.format = parse_image_format(name).ok_or(format!("unknown format \"{name}\""))?;
+    }
+    if let Some(i) = args.iter().position(|a| a == "--output") {
+        let path = args.get(i + 1).ok_or("--output requires a path")?;
+        opts.output = Some(PathBuf::from(path));
+    }
+    if let Some(i) = args.iter().position(|a| a == "--template") {
+        opts.template = args.get(i + 1).ok_or("--template requires a pattern")?.clone();
+    }
+    if let Some(i) = args.iter().position(|a| a == "--quality") {
+        opts.quality = args.get(i + 1).ok_or("--quality requires a number")?.parse()?;
     }
 
-    if cfg!(target_os = "linux") {
-        Command::new(env::current_exe()?)
-            .arg(SELF_IS_DAEMONIZED)
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .current_dir("/")
-            .spawn()?;
+    Ok(opts)
+}
+
+/// Parses the `--delay <seconds>` and `--cursor` flags out of the process args. These
+/// combine with whichever subcommand was given, so they're parsed independently of it.
+fn parse_shared_flags() -> Result<(u64, bool), AnyError> {
+    let args: Vec<String> = env::args().collect();
+
+    let delay_secs = match args.iter().position(|a| a == "--delay") {
+        Some(i) => args
+            .get(i + 1)
+            .ok_or("--delay requires a number of seconds")?
+            .parse()?,
+        None => 0,
+    };
+    let cursor = args.iter().any(|a| a == "--cursor");
+
+    Ok((delay_secs, cursor))
+}
+
+/// Flags that take a value and so consume the following argv slot.
+const VALUE_FLAGS: &[&str] = &[
+    "--delay",
+    "--screen",
+    "--format",
+    "--output",
+    "--template",
+    "--quality",
+];
+/// Standalone boolean flags.
+const BOOL_FLAGS: &[&str] = &["--cursor", "--osc52"];
+
+/// Dispatches a capture either into the foreground (OSC 52) or the detached
+/// daemonize path, mirroring whatever `run()` would otherwise do with no subcommand.
+fn dispatch_capture(
+    clipboard: &mut Clipboard,
+    use_osc52: bool,
+    opts: CaptureOptions,
+) -> Result<(), AnyError> {
+    if use_osc52 {
+        screenshot_into_clipboard(clipboard, opts)
+    } else if cfg!(target_os = "linux") {
+        daemonize(opts)
     } else {
-        screenshot_into_clipboard(&mut clipboard)?;
+        screenshot_into_clipboard(clipboard, opts)
     }
+}
 
-    Ok(())
+/// Finds the subcommand among `args`, skipping over known flags and the values they
+/// take. `--delay`/`--cursor`/`--screen`/etc. combine with whichever subcommand is
+/// given and can appear anywhere on the command line (e.g. `spit --delay 3 area`), so
+/// the subcommand is whatever non-flag token is left over, not simply `argv[1]`.
+fn find_subcommand(args: &[String]) -> Option<&str> {
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if VALUE_FLAGS.contains(&arg) {
+            i += 2;
+        } else if BOOL_FLAGS.contains(&arg) {
+            i += 1;
+        } else {
+            return Some(arg);
+        }
+    }
+    None
+}
+
+fn run() -> Result<(), AnyError> {
+    let mut clipboard = Clipboard::new()?;
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // OSC 52 writes the escape sequence to the terminal we're attached to, so it
+    // must run in the foreground rather than through the usual detached daemonize
+    // path, which redirects stdout to /dev/null.
+    let force_osc52 = args.iter().any(|a| a == "--osc52");
+    let use_osc52 = cfg!(target_os = "linux") && (force_osc52 || is_ssh_session());
+    let (delay_secs, cursor) = parse_shared_flags()?;
+
+    let base_opts = CaptureOptions {
+        osc52: use_osc52,
+        delay_secs,
+        cursor,
+        ..CaptureOptions::default()
+    };
+
+    match find_subcommand(&args) {
+        Some("help") => {
+            println!("{}", usage());
+            Ok(())
+        }
+        Some("save") => save_image_from_clipboard(&mut clipboard, &parse_save_opts()?),
+        Some("screens") => list_screens(),
+        Some("all") => dispatch_capture(
+            &mut clipboard,
+            use_osc52,
+            CaptureOptions {
+                target: ScreenTarget::All,
+                ..base_opts
+            },
+        ),
+        Some("area") => dispatch_capture(
+            &mut clipboard,
+            use_osc52,
+            CaptureOptions {
+                kind: ScreenshotKind::Area,
+                ..base_opts
+            },
+        ),
+        Some("window") => dispatch_capture(
+            &mut clipboard,
+            use_osc52,
+            CaptureOptions {
+                kind: ScreenshotKind::Window,
+                ..base_opts
+            },
+        ),
+        Some(SELF_IS_DAEMONIZED) if cfg!(target_os = "linux") => {
+            let idx = args
+                .iter()
+                .position(|a| a == SELF_IS_DAEMONIZED)
+                .expect("find_subcommand just matched this token");
+            let opts = CaptureOptions::decode(args.get(idx + 1).map(String::as_str).unwrap_or(""));
+            screenshot_into_clipboard(&mut clipboard, opts)
+        }
+        Some(subcmd) => Err(format!(r#"Unknown subcommand "{subcmd}"{usage}"#, usage = usage()).into()),
+        None => {
+            let target = match args.iter().position(|a| a == "--screen") {
+                Some(i) => ScreenTarget::Index(
+                    args.get(i + 1)
+                        .ok_or("--screen requires a screen index")?
+                        .parse()?,
+                ),
+                None => base_opts.target,
+            };
+            dispatch_capture(&mut clipboard, use_osc52, CaptureOptions { target, ..base_opts })
+        }
+    }
 }
 
 fn main() -> ExitCode {